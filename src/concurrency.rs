@@ -0,0 +1,207 @@
+//! Adaptive concurrency control for the HTTP write loop.
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use clap::Parser;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+/// Adaptive concurrency CLI config.
+#[derive(Debug, Parser)]
+pub(crate) struct ConcurrencyCLIConfig {
+    /// Upper bound for the adaptive concurrency ceiling.
+    #[clap(long, default_value_t = 256)]
+    max_concurrency: usize,
+
+    /// Multiplicative factor applied to the concurrency ceiling when
+    /// latency inflates or an error response is observed.
+    #[clap(long, default_value_t = 0.9)]
+    concurrency_backoff: f64,
+
+    /// How far (as a multiple of the EWMA baseline RTT) observed latency
+    /// may rise before the ceiling is backed off.
+    #[clap(long, default_value_t = 2.0)]
+    concurrency_rtt_threshold: f64,
+
+    /// Decay applied to the RTT baseline when a sample is *below* it.
+    ///
+    /// Closer to 1 reacts to improving latency faster.
+    #[clap(long, default_value_t = 0.2)]
+    concurrency_baseline_decay_down: f64,
+
+    /// Decay applied to the RTT baseline when a sample is *above* it.
+    ///
+    /// Kept much smaller than `concurrency_baseline_decay_down` so a
+    /// handful of slow samples can't permanently pin the baseline; it
+    /// drifts back up slowly instead, the way a true moving minimum would
+    /// after conditions recover.
+    #[clap(long, default_value_t = 0.02)]
+    concurrency_baseline_decay_up: f64,
+}
+
+/// Gradient-based adaptive concurrency controller.
+///
+/// Tracks an exponentially-weighted moving minimum round-trip time as a
+/// baseline (decaying quickly toward new lows, slowly back up toward
+/// higher samples so it can recover), compares it against each observed
+/// latency, and grows or shrinks a live concurrency ceiling accordingly.
+/// The ceiling is enforced through a [`Semaphore`], so callers acquire a
+/// permit before issuing a request and hold it (across retries) until the
+/// request is done.
+#[derive(Debug)]
+pub(crate) struct AdaptiveConcurrency {
+    semaphore: Arc<Semaphore>,
+    ceiling: AtomicUsize,
+    max_concurrency: usize,
+    backoff: f64,
+    rtt_threshold: f64,
+    baseline_decay_down: f64,
+    baseline_decay_up: f64,
+    baseline_rtt: Mutex<Option<Duration>>,
+    /// Permits still owed to `shrink()` calls that couldn't find enough
+    /// *available* permits to forget immediately because they were checked
+    /// out. Paid down as held [`Permit`]s are dropped, see its `Drop` impl.
+    pending_forgets: AtomicUsize,
+}
+
+impl AdaptiveConcurrency {
+    /// The configured upper bound on concurrency, for sizing the stream's
+    /// hard `.limit()` so source consumption itself is bounded.
+    pub(crate) fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    pub(crate) fn new(config: &ConcurrencyCLIConfig) -> Arc<Self> {
+        Arc::new(Self {
+            semaphore: Arc::new(Semaphore::new(1)),
+            ceiling: AtomicUsize::new(1),
+            max_concurrency: config.max_concurrency.max(1),
+            backoff: config.concurrency_backoff,
+            rtt_threshold: config.concurrency_rtt_threshold,
+            baseline_decay_down: config.concurrency_baseline_decay_down,
+            baseline_decay_up: config.concurrency_baseline_decay_up,
+            baseline_rtt: Mutex::new(None),
+            pending_forgets: AtomicUsize::new(0),
+        })
+    }
+
+    /// Acquire a permit under the current ceiling, waiting if it is
+    /// currently exhausted.
+    pub(crate) async fn acquire(self: &Arc<Self>) -> Permit {
+        let permit = Arc::clone(&self.semaphore)
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        Permit {
+            permit: Some(permit),
+            concurrency: Arc::clone(self),
+        }
+    }
+
+    /// Report a successful write's round-trip latency, updating the EWMA
+    /// baseline and growing the ceiling when the gradient shows headroom.
+    pub(crate) async fn report_success(&self, rtt: Duration) {
+        let mut baseline_guard = self.baseline_rtt.lock().await;
+        let baseline = match *baseline_guard {
+            None => rtt,
+            Some(baseline) => {
+                // Decay quickly toward a new low so the baseline tracks a
+                // moving minimum, but also drift slowly toward samples
+                // above it, so a transient spike can't pin the baseline
+                // below reality forever.
+                let decay = if rtt < baseline {
+                    self.baseline_decay_down
+                } else {
+                    self.baseline_decay_up
+                };
+                let baseline_secs = baseline.as_secs_f64();
+                let rtt_secs = rtt.as_secs_f64();
+                Duration::from_secs_f64(baseline_secs + decay * (rtt_secs - baseline_secs))
+            }
+        };
+        *baseline_guard = Some(baseline);
+        drop(baseline_guard);
+
+        let gradient = baseline.as_secs_f64() / rtt.as_secs_f64().max(f64::EPSILON);
+        if gradient * self.rtt_threshold >= 1.0 {
+            self.grow();
+        } else {
+            self.shrink();
+        }
+    }
+
+    /// Report a 429/5xx response, multiplicatively shrinking the ceiling.
+    pub(crate) fn report_error(&self) {
+        self.shrink();
+    }
+
+    fn grow(&self) {
+        let updated = self
+            .ceiling
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| {
+                (c < self.max_concurrency).then_some(c + 1)
+            });
+        if let Ok(prev) = updated {
+            self.semaphore.add_permits(1);
+            debug!(ceiling = prev + 1, "grew concurrency ceiling");
+        }
+    }
+
+    fn shrink(&self) {
+        let updated = self
+            .ceiling
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| {
+                let next = (((c as f64) * self.backoff) as usize).max(1);
+                (next < c).then_some(next)
+            });
+        if let Ok(prev) = updated {
+            let next = (((prev as f64) * self.backoff) as usize).max(1);
+            let wanted = prev - next;
+
+            // `forget_permits` can only discard *available* permits; under
+            // load most are checked out, so the shortfall is recorded as
+            // debt and paid down as held `Permit`s are dropped instead of
+            // returned, so the effective capacity actually reaches `next`.
+            let forgotten = self.semaphore.forget_permits(wanted);
+            let owed = wanted - forgotten;
+            if owed > 0 {
+                self.pending_forgets.fetch_add(owed, Ordering::SeqCst);
+            }
+
+            debug!(ceiling = next, "shrank concurrency ceiling");
+        }
+    }
+}
+
+/// An acquired concurrency slot. Dropping it normally returns the permit to
+/// the semaphore, unless `shrink()` is still owed permits it couldn't
+/// forget immediately, in which case it's forgotten instead.
+pub(crate) struct Permit {
+    permit: Option<OwnedSemaphorePermit>,
+    concurrency: Arc<AdaptiveConcurrency>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+
+        let paid_down_debt = self
+            .concurrency
+            .pending_forgets
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |owed| {
+                (owed > 0).then_some(owed - 1)
+            })
+            .is_ok();
+
+        if paid_down_debt {
+            permit.forget();
+        }
+    }
+}
@@ -1,12 +1,16 @@
 use std::{
     fmt::Write,
+    path::PathBuf,
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{Context, Error, Result};
 use clap::Parser;
-use rand::{Rng, RngCore, distr::Alphabetic};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::schema::Schema;
 
 /// Data generator CLI config.
 #[derive(Debug, Parser)]
@@ -14,16 +18,61 @@ pub(crate) struct DataCLIConfig {
     /// Number of lines per submission batch.
     #[clap(long, default_value_t = 10_000)]
     batch_lines: usize,
+
+    /// Path to a TOML or JSON schema file describing the measurement, tags,
+    /// and fields to generate.
+    ///
+    /// Defaults to a small built-in demo schema.
+    #[clap(long)]
+    schema_file: Option<PathBuf>,
+
+    /// Base RNG seed for deterministic, reproducible generation.
+    ///
+    /// Each batch is seeded with this value XORed with its batch index, so
+    /// batches generated concurrently stay independent yet reproducible.
+    /// Defaults to non-reproducible OS randomness.
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// Starting timestamp (nanoseconds since the epoch) for generated
+    /// lines, instead of the wall-clock time.
+    #[clap(long)]
+    start_timestamp: Option<u64>,
+
+    /// Nanosecond increment applied to the timestamp after each generated
+    /// line.
+    ///
+    /// Only takes effect alongside `--start-timestamp`.
+    #[clap(long, default_value_t = 1)]
+    timestamp_step: u64,
 }
 
-pub(crate) async fn generate_batch(config: &Arc<DataCLIConfig>) -> Result<String> {
+pub(crate) async fn generate_batch(config: &Arc<DataCLIConfig>, batch_index: usize) -> Result<String> {
     let config = Arc::clone(config);
     let lines = tokio::task::spawn_blocking(move || {
-        let mut rng = rand::rng();
+        let schema = match &config.schema_file {
+            Some(path) => Schema::from_file(path).context("load schema")?,
+            None => Schema::demo(),
+        };
+
+        let mut clock = Clock::new(&config, batch_index);
         let mut lines = String::new();
-        for _ in 0..config.batch_lines {
-            gen_line(&mut lines, &mut rng).context("gen line")?;
+
+        match config.seed {
+            Some(seed) => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed ^ batch_index as u64);
+                for _ in 0..config.batch_lines {
+                    gen_line(&mut lines, &mut rng, &schema, &mut clock).context("gen line")?;
+                }
+            }
+            None => {
+                let mut rng = rand::rng();
+                for _ in 0..config.batch_lines {
+                    gen_line(&mut lines, &mut rng, &schema, &mut clock).context("gen line")?;
+                }
+            }
         }
+
         Result::<_, Error>::Ok(lines)
     })
     .await
@@ -32,33 +81,98 @@ pub(crate) async fn generate_batch(config: &Arc<DataCLIConfig>) -> Result<String
     Ok(lines)
 }
 
-fn gen_line<W, R>(w: &mut W, rng: &mut R) -> Result<()>
+/// Source of line timestamps: either the wall clock, or a deterministic
+/// counter seeded per batch so reruns produce identical output.
+enum Clock {
+    Wall,
+    Fixed { next: u64, step: u64 },
+}
+
+impl Clock {
+    fn new(config: &DataCLIConfig, batch_index: usize) -> Self {
+        match config.start_timestamp {
+            Some(start) => {
+                let offset = batch_index as u64 * config.batch_lines as u64 * config.timestamp_step;
+                Self::Fixed {
+                    next: start + offset,
+                    step: config.timestamp_step,
+                }
+            }
+            None => Self::Wall,
+        }
+    }
+
+    fn next(&mut self) -> u128 {
+        match self {
+            Self::Wall => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time should go forward")
+                .as_nanos(),
+            Self::Fixed { next, step } => {
+                let time = *next;
+                *next += *step;
+                u128::from(time)
+            }
+        }
+    }
+}
+
+fn gen_line<W, R>(w: &mut W, rng: &mut R, schema: &Schema, clock: &mut Clock) -> Result<()>
 where
     W: Write,
     R: RngCore,
 {
-    let tag: String = rng
-        .sample_iter(Alphabetic)
-        .take(1)
-        .map(char::from)
-        .collect();
-    let field_s: String = rng
-        .sample_iter(Alphabetic)
-        .take(8)
-        .map(char::from)
-        .collect();
-    let field_i: i64 = rng.random();
-    let field_u: u64 = rng.random();
-    let field_f: f64 = rng.random();
-    let field_b: bool = rng.random();
-    let time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("time should go forward")
-        .as_nanos();
-
-    writeln!(
-        w,
-        "table,tag={tag} field_s=\"{field_s}\",field_i={field_i}i,field_u={field_u}u,field_f={field_f},field_b={field_b} {time}"
-    )
-    .context("write")
+    write!(w, "{}", schema.measurement).context("write measurement")?;
+    for tag in &schema.tags {
+        write!(w, ",{}={}", tag.name, tag.gen_value(rng)).context("write tag")?;
+    }
+
+    write!(w, " ").context("write separator")?;
+    for (i, field) in schema.fields.iter().enumerate() {
+        if i > 0 {
+            write!(w, ",").context("write separator")?;
+        }
+        write!(w, "{}=", field.name).context("write field name")?;
+        field.write_value(w, rng).context("write field value")?;
+    }
+
+    let time = clock.next();
+    writeln!(w, " {time}").context("write timestamp")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn generate_batch_is_reproducible_given_a_seed_and_start_timestamp() {
+        let config = Arc::new(DataCLIConfig {
+            batch_lines: 16,
+            schema_file: None,
+            seed: Some(42),
+            start_timestamp: Some(1_700_000_000_000_000_000),
+            timestamp_step: 1,
+        });
+
+        let first = generate_batch(&config, 0).await.expect("generate batch");
+        let second = generate_batch(&config, 0).await.expect("generate batch");
+
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn generate_batch_differs_across_batch_indices() {
+        let config = Arc::new(DataCLIConfig {
+            batch_lines: 16,
+            schema_file: None,
+            seed: Some(42),
+            start_timestamp: Some(1_700_000_000_000_000_000),
+            timestamp_step: 1,
+        });
+
+        let first = generate_batch(&config, 0).await.expect("generate batch");
+        let second = generate_batch(&config, 1).await.expect("generate batch");
+
+        assert_ne!(first, second);
+    }
 }
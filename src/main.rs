@@ -1,56 +1,47 @@
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{sync::Arc, time::Instant};
 
 use anyhow::{Context, Error, Result};
-use async_compression::tokio::write::GzipEncoder;
-use bytes::Bytes;
-use clap::{Parser, ValueEnum};
+use clap::Parser;
+use compression::{CompressionCLIConfig, compress};
+use concurrency::{AdaptiveConcurrency, ConcurrencyCLIConfig};
 use data::{DataCLIConfig, generate_batch};
 use futures_concurrency::{prelude::ConcurrentStream, stream::StreamExt};
-use http::{
-    StatusCode,
-    header::{ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE},
-};
 use logging::{LoggingCLIConfig, setup_logging};
+use metrics::Metrics;
+use ratelimit::{RateLimitCLIConfig, RateLimiter};
 use reqwest::Client;
-use retry::retry;
-use tokio::io::AsyncWriteExt;
+use sink::{Sink, SinkCLIConfig, build_sink};
+use tokio::time::interval;
 use tracing::info;
 
+mod compression;
+mod concurrency;
 mod data;
 mod logging;
+mod metrics;
+mod ratelimit;
 mod retry;
+mod schema;
+mod sink;
+
+/// How often the run summary is logged while the write loop is running.
+const SUMMARY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
 
 #[derive(Debug, Parser)]
 struct Args {
-    /// InfluxDB location (schema + hostname, potentially port).
-    #[clap(long)]
-    url: String,
-
-    /// InfluxDB org.
-    #[clap(long)]
-    org: String,
-
-    /// InfluxDB bucket.
-    #[clap(long)]
-    bucket: String,
-
-    /// Auth token.
-    #[clap(long)]
-    token: String,
-
     /// Number of batches.
     ///
     /// Defaults to "infinite".
     #[clap(long)]
     batches: Option<usize>,
 
-    /// Concurrency limit.
-    #[clap(long, default_value = "4")]
-    concurrency_limit: NonZeroUsize,
+    /// Compression args.
+    #[clap(flatten)]
+    compression_cfg: CompressionCLIConfig,
 
-    /// GZip compression level of HTTP data.
-    #[clap(long)]
-    compression_level: Option<CompressionLevel>,
+    /// Adaptive concurrency args.
+    #[clap(flatten)]
+    concurrency_cfg: ConcurrencyCLIConfig,
 
     /// Data gen args.
     #[clap(flatten)]
@@ -59,6 +50,14 @@ struct Args {
     /// Logging args.
     #[clap(flatten)]
     logging_cfg: LoggingCLIConfig,
+
+    /// Rate limiting args.
+    #[clap(flatten)]
+    rate_limit_cfg: RateLimitCLIConfig,
+
+    /// Output sink args.
+    #[clap(flatten)]
+    sink_cfg: SinkCLIConfig,
 }
 
 #[tokio::main]
@@ -66,93 +65,72 @@ async fn main() -> Result<()> {
     let args = Args::parse();
     setup_logging(args.logging_cfg).context("set up logging")?;
 
+    let metrics = Metrics::new();
+
     let client = Client::builder().build().context("build client")?;
+    let sink = build_sink(args.sink_cfg, &args.compression_cfg, client, Arc::clone(&metrics))
+        .await
+        .context("build sink")?;
 
     let data_cfg = Arc::new(args.data_cfg);
+    let concurrency = AdaptiveConcurrency::new(&args.concurrency_cfg);
+    let rate_limiter = RateLimiter::new(&args.rate_limit_cfg);
+
+    let summary_task = tokio::spawn({
+        let metrics = Arc::clone(&metrics);
+        async move {
+            let mut interval = interval(SUMMARY_INTERVAL);
+            loop {
+                interval.tick().await;
+                metrics.log_summary().await;
+            }
+        }
+    });
 
     futures::stream::iter(0..args.batches.unwrap_or(usize::MAX))
         .co()
-        .limit(Some(args.concurrency_limit))
+        .limit(Some(concurrency.max_concurrency()))
         .map(async |i| {
-            let lines = generate_batch(&data_cfg).await.context("generate batch")?;
-
-            let (content_encoding, body) = match args.compression_level {
-                None => ("identity", lines.into_bytes()),
-                Some(compression_level) => {
-                    let mut encoder =
-                        GzipEncoder::with_quality(Vec::new(), compression_level.into());
-                    encoder
-                        .write_all(lines.as_bytes())
-                        .await
-                        .context("compress data")?;
-                    encoder.shutdown().await.context("flush encoder")?;
-                    let body = encoder.into_inner();
-                    ("gzip", body)
-                }
-            };
+            let permit = concurrency.acquire().await;
 
-            let body = Bytes::from(body);
+            let lines = generate_batch(&data_cfg, i).await.context("generate batch")?;
+            let line_count = lines.lines().count() as u64;
+            let uncompressed_bytes = lines.len() as u64;
 
-            Result::<_, Error>::Ok((i, content_encoding, body))
+            rate_limiter.acquire(line_count).await;
+
+            let (content_encoding, body) = compress(&args.compression_cfg, lines.into_bytes())
+                .await
+                .context("compress batch")?;
+
+            Result::<_, Error>::Ok((i, content_encoding, body, line_count, uncompressed_bytes, permit))
         })
         .try_for_each(async |res| {
-            let (i, content_encoding, body) = res?;
-
-            let request = client
-                .post(format!("{}/api/v2/write", args.url.trim_end_matches("/")))
-                .query(&[
-                    ("org", args.org.as_str()),
-                    ("bucket", args.bucket.as_str()),
-                    ("precision", "ns"),
-                ])
-                .header(AUTHORIZATION, format!("Token {}", args.token))
-                .header(ACCEPT, "application/json")
-                .header(CONTENT_ENCODING, content_encoding)
-                .header(CONTENT_TYPE, "text/plain; charset=utf-8")
-                .body(body)
-                .build()
-                .context("build request")?;
-
-            retry(
-                "send request",
-                async || {
-                    let request = request.try_clone().expect("can clone request");
-                    let resp = client.execute(request).await?;
-                    resp.error_for_status()?;
-                    Ok(())
-                },
-                |err: &reqwest::Error| {
-                    err.status()
-                        .map(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS)
-                        .unwrap_or_default()
-                },
-            )
-            .await
-            .context("retry request")?;
+            let (i, content_encoding, body, line_count, uncompressed_bytes, permit) = res?;
+            let compressed_bytes = body.len() as u64;
+
+            let start = Instant::now();
+            let result = sink.write_batch(content_encoding, body).await;
+            let latency = start.elapsed();
+            match &result {
+                Ok(()) => {
+                    concurrency.report_success(latency).await;
+                    metrics
+                        .record_batch(line_count, uncompressed_bytes, compressed_bytes, latency)
+                        .await;
+                }
+                Err(_) => concurrency.report_error(),
+            }
+            drop(permit);
+            result.context("write batch")?;
 
             info!(batch = i + 1, "sent batch");
             Result::<(), Error>::Ok(())
         })
         .await?;
 
-    Ok(())
-}
-
-#[derive(Debug, Clone, Copy, ValueEnum)]
-enum CompressionLevel {
-    Fastest,
-    Best,
-    Default,
-}
+    summary_task.abort();
+    metrics.log_summary().await;
 
-impl From<CompressionLevel> for async_compression::Level {
-    fn from(level: CompressionLevel) -> Self {
-        use async_compression::Level;
-
-        match level {
-            CompressionLevel::Fastest => Level::Fastest,
-            CompressionLevel::Best => Level::Best,
-            CompressionLevel::Default => Level::Default,
-        }
-    }
+    Ok(())
 }
@@ -0,0 +1,102 @@
+//! Run-summary metrics: counts, bytes, and latency percentiles for the
+//! write loop.
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use hdrhistogram::Histogram;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Latencies are tracked in microseconds, from 1us up to a minute, at 3
+/// significant figures; this bounds the histogram to about 136 KiB
+/// (17,408 `u64` counters) regardless of how many batches are recorded.
+const MAX_LATENCY_MICROS: u64 = 60_000_000;
+const SIGNIFICANT_FIGURES: u8 = 3;
+
+/// Aggregates counters across the concurrent write stream so a summary can
+/// be reported periodically and at shutdown.
+///
+/// Latencies are kept in a bounded [`Histogram`] rather than a growing
+/// `Vec`, so memory use and `log_summary`'s cost stay flat over an
+/// arbitrarily long (potentially infinite, see `--batches`) run.
+#[derive(Debug)]
+pub(crate) struct Metrics {
+    start: Instant,
+    lines_sent: AtomicU64,
+    uncompressed_bytes: AtomicU64,
+    compressed_bytes: AtomicU64,
+    retries: AtomicU64,
+    latencies: Mutex<Histogram<u64>>,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Arc<Self> {
+        let latencies = Histogram::new_with_bounds(1, MAX_LATENCY_MICROS, SIGNIFICANT_FIGURES)
+            .expect("static histogram bounds are valid");
+
+        Arc::new(Self {
+            start: Instant::now(),
+            lines_sent: AtomicU64::new(0),
+            uncompressed_bytes: AtomicU64::new(0),
+            compressed_bytes: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            latencies: Mutex::new(latencies),
+        })
+    }
+
+    /// Record one completed batch submission.
+    pub(crate) async fn record_batch(
+        &self,
+        lines: u64,
+        uncompressed_bytes: u64,
+        compressed_bytes: u64,
+        latency: Duration,
+    ) {
+        self.lines_sent.fetch_add(lines, Ordering::Relaxed);
+        self.uncompressed_bytes
+            .fetch_add(uncompressed_bytes, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed_bytes, Ordering::Relaxed);
+
+        let micros = latency.as_micros().min(MAX_LATENCY_MICROS as u128) as u64;
+        let _ = self.latencies.lock().await.record(micros.max(1));
+    }
+
+    /// Record one retried request.
+    pub(crate) fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Log the current run summary via `tracing`.
+    pub(crate) async fn log_summary(&self) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let lines_sent = self.lines_sent.load(Ordering::Relaxed);
+        let uncompressed_bytes = self.uncompressed_bytes.load(Ordering::Relaxed);
+        let compressed_bytes = self.compressed_bytes.load(Ordering::Relaxed);
+        let retries = self.retries.load(Ordering::Relaxed);
+
+        let (avg_latency_ms, p99_latency_ms) = {
+            let latencies = self.latencies.lock().await;
+            (
+                latencies.mean() / 1000.0,
+                latencies.value_at_quantile(0.99) as f64 / 1000.0,
+            )
+        };
+
+        info!(
+            lines_sent,
+            uncompressed_bytes,
+            compressed_bytes,
+            retries,
+            avg_latency_ms,
+            p99_latency_ms,
+            lines_per_second = lines_sent as f64 / elapsed.max(f64::EPSILON),
+            "run summary"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Pluggable output sinks for generated write batches.
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use bytes::Bytes;
+use clap::{Parser, ValueEnum};
+use http::{
+    StatusCode,
+    header::{ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE},
+};
+use rdkafka::{
+    ClientConfig,
+    producer::{FutureProducer, FutureRecord},
+};
+use reqwest::Client;
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::Mutex,
+};
+
+use crate::{compression::CompressionCLIConfig, metrics::Metrics, retry::retry};
+
+/// Output sink CLI config.
+#[derive(Debug, Parser)]
+pub(crate) struct SinkCLIConfig {
+    /// Where generated batches are written.
+    #[clap(long, value_enum, default_value_t = SinkKind::Influx)]
+    output: SinkKind,
+
+    /// InfluxDB location (schema + hostname, potentially port).
+    ///
+    /// Required when `--output influx`.
+    #[clap(long)]
+    url: Option<String>,
+
+    /// InfluxDB org.
+    ///
+    /// Required when `--output influx`.
+    #[clap(long)]
+    org: Option<String>,
+
+    /// InfluxDB bucket.
+    ///
+    /// Required when `--output influx`.
+    #[clap(long)]
+    bucket: Option<String>,
+
+    /// Auth token.
+    ///
+    /// Required when `--output influx`.
+    #[clap(long)]
+    token: Option<String>,
+
+    /// File to append generated batches to.
+    ///
+    /// Used with `--output file`. Omit to write to stdout.
+    #[clap(long)]
+    output_file: Option<PathBuf>,
+
+    /// Kafka bootstrap brokers, comma-separated.
+    ///
+    /// Required when `--output kafka`.
+    #[clap(long)]
+    kafka_brokers: Option<String>,
+
+    /// Kafka topic to publish batches to.
+    ///
+    /// Required when `--output kafka`.
+    #[clap(long)]
+    kafka_topic: Option<String>,
+}
+
+/// Which output sink to send generated batches to.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SinkKind {
+    Influx,
+    File,
+    Kafka,
+}
+
+/// A destination for generated write batches.
+///
+/// Decouples data generation from transport: the same generator can feed
+/// an InfluxDB bucket, a local file for fixture capture, or a message
+/// broker for pipeline testing.
+pub(crate) trait Sink: Send + Sync {
+    async fn write_batch(&self, content_encoding: &str, body: Bytes) -> Result<()>;
+}
+
+/// Build the sink selected by [`SinkCLIConfig::output`].
+pub(crate) async fn build_sink(
+    config: SinkCLIConfig,
+    compression: &CompressionCLIConfig,
+    client: Client,
+    metrics: Arc<Metrics>,
+) -> Result<AnySink> {
+    if matches!(config.output, SinkKind::Kafka) && compression.is_enabled() {
+        bail!("`--output kafka` does not support compressed bodies; drop `--compression-algorithm`");
+    }
+
+    match config.output {
+        SinkKind::Influx => Ok(AnySink::Influx(InfluxSink {
+            client,
+            url: config.url.context("`--url` is required for `--output influx`")?,
+            org: config.org.context("`--org` is required for `--output influx`")?,
+            bucket: config
+                .bucket
+                .context("`--bucket` is required for `--output influx`")?,
+            token: config
+                .token
+                .context("`--token` is required for `--output influx`")?,
+            metrics,
+        })),
+        SinkKind::File => Ok(AnySink::File(FileSink::new(config.output_file).await?)),
+        SinkKind::Kafka => Ok(AnySink::Kafka(KafkaSink::new(
+            config
+                .kafka_brokers
+                .context("`--kafka-brokers` is required for `--output kafka`")?,
+            config
+                .kafka_topic
+                .context("`--kafka-topic` is required for `--output kafka`")?,
+        )?)),
+    }
+}
+
+/// One of the concrete [`Sink`] implementations, dispatched without a trait
+/// object so `write_batch` stays a plain `async fn`.
+pub(crate) enum AnySink {
+    Influx(InfluxSink),
+    File(FileSink),
+    Kafka(KafkaSink),
+}
+
+impl Sink for AnySink {
+    async fn write_batch(&self, content_encoding: &str, body: Bytes) -> Result<()> {
+        match self {
+            Self::Influx(sink) => sink.write_batch(content_encoding, body).await,
+            Self::File(sink) => sink.write_batch(content_encoding, body).await,
+            Self::Kafka(sink) => sink.write_batch(content_encoding, body).await,
+        }
+    }
+}
+
+/// Writes batches to an InfluxDB v2 `/api/v2/write` endpoint, retrying
+/// transient failures.
+pub(crate) struct InfluxSink {
+    client: Client,
+    url: String,
+    org: String,
+    bucket: String,
+    token: String,
+    metrics: Arc<Metrics>,
+}
+
+impl Sink for InfluxSink {
+    async fn write_batch(&self, content_encoding: &str, body: Bytes) -> Result<()> {
+        let request = self
+            .client
+            .post(format!("{}/api/v2/write", self.url.trim_end_matches("/")))
+            .query(&[
+                ("org", self.org.as_str()),
+                ("bucket", self.bucket.as_str()),
+                ("precision", "ns"),
+            ])
+            .header(AUTHORIZATION, format!("Token {}", self.token))
+            .header(ACCEPT, "application/json")
+            .header(CONTENT_ENCODING, content_encoding)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(body)
+            .build()
+            .context("build request")?;
+
+        retry(
+            "send request",
+            async || {
+                let request = request.try_clone().expect("can clone request");
+                let resp = self.client.execute(request).await?;
+                resp.error_for_status()?;
+                Ok(())
+            },
+            |err: &reqwest::Error| {
+                let retryable = err
+                    .status()
+                    .map(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS)
+                    .unwrap_or_default();
+                if retryable {
+                    self.metrics.record_retry();
+                }
+                retryable
+            },
+        )
+        .await
+        .context("retry request")
+    }
+}
+
+/// Writes batches to a local file (or stdout), one batch after another.
+///
+/// Useful for capturing generated data as a fixture without standing up a
+/// server.
+pub(crate) struct FileSink {
+    writer: Mutex<Box<dyn AsyncWrite + Unpin + Send>>,
+}
+
+impl FileSink {
+    pub(crate) async fn new(path: Option<PathBuf>) -> Result<Self> {
+        let writer: Box<dyn AsyncWrite + Unpin + Send> = match path {
+            Some(path) => Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .await
+                    .with_context(|| format!("open output file `{}`", path.display()))?,
+            ),
+            None => Box::new(tokio::io::stdout()),
+        };
+
+        Ok(Self {
+            writer: Mutex::new(writer),
+        })
+    }
+}
+
+impl Sink for FileSink {
+    async fn write_batch(&self, _content_encoding: &str, body: Bytes) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.write_all(&body).await.context("write batch")?;
+        writer.flush().await.context("flush output")
+    }
+}
+
+/// Publishes batches to a Kafka topic, one message per batch.
+pub(crate) struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl KafkaSink {
+    pub(crate) fn new(brokers: String, topic: String) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &brokers)
+            .create()
+            .context("build kafka producer")?;
+
+        Ok(Self { producer, topic })
+    }
+}
+
+impl Sink for KafkaSink {
+    async fn write_batch(&self, content_encoding: &str, body: Bytes) -> Result<()> {
+        if content_encoding != "identity" {
+            bail!("kafka sink does not support compressed bodies (got `{content_encoding}`)");
+        }
+
+        let record = FutureRecord::<(), [u8]>::to(&self.topic).payload(body.as_ref());
+        self.producer
+            .send(record, Duration::from_secs(30))
+            .await
+            .map_err(|(err, _msg)| err)
+            .context("send kafka record")?;
+
+        Ok(())
+    }
+}